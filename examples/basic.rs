@@ -7,7 +7,9 @@ fn main() {
     app.middleware(RootLogger::new());
 
     let static_files = StaticFiles::new(".").unwrap();
-    app.at("/static/*").get(static_files);
+    app.at("/static/*")
+        .get(static_files.clone())
+        .head(static_files);
 
     app.serve("127.0.0.1:8000").unwrap();
 }
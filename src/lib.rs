@@ -3,32 +3,97 @@
 //! runs in the context of a tokio runtime (which is the case when you run tide with hyper, the
 //! default http server implementation).
 //!
+//! `StaticFiles` answers `HEAD` requests (e.g. to support conditional GET and `Content-Length`
+//! probing) as well as `GET`, but only for whichever methods the route is actually mounted with —
+//! mount both if you want both:
+//!
 //! ```
 //! # use tide_static_files::StaticFiles;
 //! #
 //! # fn main() -> Result<(), Box<dyn std::error::Error>> {
 //!   let mut app = tide::new();
 //!
-//!   app.at("/assets/*path").get(StaticFiles::new("/var/lib/my-app/assets"));
+//!   app.at("/assets/*path")
+//!       .get(StaticFiles::new("/var/lib/my-app/assets"))
+//!       .head(StaticFiles::new("/var/lib/my-app/assets"));
 //!
 //!   # Ok(())
 //! # }
 //! ```
 
-use http::StatusCode;
+use async_std::prelude::*;
+use http::{header, HeaderMap, Method, StatusCode};
+use http_service::Body;
+use percent_encoding::{utf8_percent_encode, AsciiSet, CONTROLS};
 use regex::Regex;
+use std::fs::File;
 use std::path::{Path, PathBuf};
 use tide::{Response, Request};
 
+mod file_stream;
+mod utils;
+
+use file_stream::new_file_stream;
+use utils::{
+    escape_html, http_date, is_fresh, parse_accept_encoding, parse_range, precompressed_sibling,
+    weak_etag, RangeRequest,
+};
+
+/// Hand an `async_std::fs::File` we've already opened and stat'd asynchronously over to
+/// `file_stream`'s blocking thread pool, without a second (blocking) open.
+#[cfg(unix)]
+fn into_std_file(file: async_std::fs::File) -> File {
+    use std::os::unix::io::{FromRawFd, IntoRawFd};
+    // SAFETY: `into_raw_fd` gives us unique ownership of the descriptor, which `from_raw_fd`
+    // immediately takes back over as a `std::fs::File` — no double-close, no use-after-free.
+    unsafe { File::from_raw_fd(file.into_raw_fd()) }
+}
+
+#[cfg(windows)]
+fn into_std_file(file: async_std::fs::File) -> File {
+    use std::os::windows::io::{FromRawHandle, IntoRawHandle};
+    // SAFETY: see the unix impl above; same single-owner handoff via `IntoRawHandle`.
+    unsafe { File::from_raw_handle(file.into_raw_handle()) }
+}
+
+/// Whether `path` exists and is a regular file, without blocking the executor thread.
+async fn is_file(path: &Path) -> bool {
+    async_std::fs::metadata(path)
+        .await
+        .map(|metadata| metadata.is_file())
+        .unwrap_or(false)
+}
+
+/// Characters that need percent-encoding in a directory listing's `href`s, on top of the
+/// controls: anything that would otherwise confuse an `<a>` tag or the URL parser.
+const HREF_ENCODE_SET: &AsciiSet = &CONTROLS
+    .add(b' ')
+    .add(b'"')
+    .add(b'\'')
+    .add(b'<')
+    .add(b'>')
+    .add(b'`')
+    .add(b'#')
+    .add(b'?')
+    .add(b'{')
+    .add(b'}')
+    .add(b'&')
+    .add(b'%');
+
 /// A struct that serves a directory.
 ///
+/// Mount it for `GET` and, if you also want `HEAD` to work, mount it for `HEAD` too — it is
+/// keyed off the request method, not off which methods the route answers to.
+///
 /// ```
 /// # use tide_static_files::StaticFiles;
 /// #
 /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
 ///   let mut app = tide::new();
 ///
-///   app.at("/assets/*path").get(StaticFiles::new("/var/lib/my-app/assets"));
+///   app.at("/assets/*path")
+///       .get(StaticFiles::new("/var/lib/my-app/assets"))
+///       .head(StaticFiles::new("/var/lib/my-app/assets"));
 ///
 ///   # Ok(())
 /// # }
@@ -43,40 +108,309 @@ use tide::{Response, Request};
 pub struct StaticFiles {
     base: PathBuf,
     path_traversal_matcher: Regex,
+    index_file: Option<String>,
+    show_listing: bool,
+    not_found_fallback: Option<PathBuf>,
+    not_found_status: StatusCode,
+    server_error_body: String,
 }
 
-use async_std::fs::File;
-use async_std::io::BufReader;
-
 impl StaticFiles {
     /// Create a StaticFiles handler for the directory at the provided path.
     pub fn new(path: &str) -> Self {
         StaticFiles {
             base: Path::new(path).into(),
             path_traversal_matcher: Self::path_traversal_regex(),
+            index_file: None,
+            show_listing: false,
+            not_found_fallback: None,
+            not_found_status: StatusCode::NOT_FOUND,
+            server_error_body: "internal server error".to_owned(),
+        }
+    }
+
+    /// Serve this file instead of a plain 404 body when a request does not resolve to a real
+    /// file. Combined with [`not_found_status`](Self::not_found_status) set to `200`, this is
+    /// what a single-page app needs to let its client-side router handle deep links while still
+    /// serving real assets directly.
+    pub fn not_found_fallback(mut self, path: impl Into<PathBuf>) -> Self {
+        self.not_found_fallback = Some(path.into());
+        self
+    }
+
+    /// The status used for the response when a request does not resolve to a real file (defaults
+    /// to `404 Not Found`).
+    pub fn not_found_status(mut self, status: StatusCode) -> Self {
+        self.not_found_status = status;
+        self
+    }
+
+    /// The response body used when a file exists but could not be read (defaults to
+    /// `"internal server error"`).
+    pub fn server_error_body(mut self, body: impl Into<String>) -> Self {
+        self.server_error_body = body.into();
+        self
+    }
+
+    /// Build the response for a request that didn't resolve to a real file, serving
+    /// `not_found_fallback` when one is configured.
+    async fn not_found_response(&self, method: &Method) -> Response {
+        if let Some(fallback) = &self.not_found_fallback {
+            match self.read_fallback_file(fallback, method).await {
+                Ok(response) => return response,
+                Err(err) => log::warn!("Error reading not_found_fallback file: {:?}", err),
+            }
         }
+
+        Response::new(self.not_found_status.into())
+    }
+
+    async fn read_fallback_file(&self, path: &Path, method: &Method) -> std::io::Result<Response> {
+        let file = async_std::fs::File::open(path).await?;
+        let size = file.metadata().await?.len();
+        let mime = mime_guess::from_path(path).first_or_text_plain();
+        let body = if *method == Method::HEAD {
+            Body::empty()
+        } else {
+            Body::from_stream(new_file_stream(into_std_file(file), 0..size))
+        };
+
+        Ok(Response::new(self.not_found_status.into())
+            .body(body)
+            .set_mime(mime)
+            .set_header(header::CONTENT_LENGTH, size.to_string()))
     }
 
-    async fn serve<'a>(&'a self, path: &'a str) -> Result<Response, Response> {
+    fn server_error_response(&self) -> Response {
+        Response::new(StatusCode::INTERNAL_SERVER_ERROR.into())
+            .body(Body::from(self.server_error_body.clone().into_bytes()))
+            .set_mime(mime::TEXT_PLAIN)
+    }
+
+    /// Serve this file (relative to the resolved directory) when a request resolves to a
+    /// directory, e.g. `"index.html"`.
+    pub fn index_file(mut self, name: &str) -> Self {
+        self.index_file = Some(name.to_owned());
+        self
+    }
+
+    /// Generate an HTML directory listing when a request resolves to a directory with no index
+    /// file (or no `index_file` configured).
+    pub fn show_listing(mut self, show_listing: bool) -> Self {
+        self.show_listing = show_listing;
+        self
+    }
+
+    async fn serve<'a>(
+        &'a self,
+        path: &'a str,
+        method: &'a Method,
+        headers: &'a HeaderMap,
+    ) -> Result<Response, Response> {
         if self.path_traversal_matcher.is_match(path) {
-            return Ok(not_found_response());
+            // A path traversal attempt is not "just" a missing file: never answer it with the
+            // configured `not_found_fallback`.
+            return Ok(Response::new(StatusCode::NOT_FOUND.into()));
+        }
+
+        let resolved = self.base.join(path);
+
+        let is_dir = async_std::fs::metadata(&resolved)
+            .await
+            .map(|metadata| metadata.is_dir())
+            .unwrap_or(false);
+
+        if is_dir {
+            return self.serve_dir(&resolved, method, headers).await;
+        }
+
+        self.serve_file(&resolved, method, headers).await
+    }
+
+    async fn serve_dir<'a>(
+        &'a self,
+        dir: &'a Path,
+        method: &'a Method,
+        headers: &'a HeaderMap,
+    ) -> Result<Response, Response> {
+        if let Some(index_file) = &self.index_file {
+            let index_path = dir.join(index_file);
+
+            if async_std::fs::metadata(&index_path).await.is_ok() {
+                return self.serve_file(&index_path, method, headers).await;
+            }
+        }
+
+        if self.show_listing {
+            return self.render_listing(dir).await;
         }
 
-        let path = self.base.join(path);
+        Ok(self.not_found_response(method).await)
+    }
 
+    async fn serve_file<'a>(
+        &'a self,
+        path: &'a Path,
+        method: &'a Method,
+        headers: &'a HeaderMap,
+    ) -> Result<Response, Response> {
         let mime = mime_guess::from_path(&path).first_or_text_plain();
 
-        let file = BufReader::new(File::open(path).await
-            .map_err(|err| {
+        let accepted_encodings = headers
+            .get(header::ACCEPT_ENCODING)
+            .and_then(|value| value.to_str().ok())
+            .map(parse_accept_encoding)
+            .unwrap_or_default();
+
+        let br_path = precompressed_sibling(path, "br");
+        let gzip_path = precompressed_sibling(path, "gz");
+
+        // Brotli is preferred over gzip whenever both are accepted and both siblings exist; this
+        // is a fixed preference order, not a ranking by the client's `q` weights (see
+        // `AcceptedEncodings`).
+        let (actual_path, content_encoding): (PathBuf, Option<&'static str>) =
+            if accepted_encodings.br && is_file(&br_path).await {
+                (br_path, Some("br"))
+            } else if accepted_encodings.gzip && is_file(&gzip_path).await {
+                (gzip_path, Some("gzip"))
+            } else {
+                (path.to_owned(), None)
+            };
+
+        let async_file = match async_std::fs::File::open(&actual_path).await {
+            Ok(file) => file,
+            Err(err) => {
                 log::warn!("Error reading file: {:?}", err);
-                not_found_response()
-            })?);
+                return Ok(self.not_found_response(method).await);
+            }
+        };
+
+        let metadata = match async_file.metadata().await {
+            Ok(metadata) => metadata,
+            Err(err) => {
+                log::warn!("Error reading file metadata: {:?}", err);
+                return Ok(self.server_error_response());
+            }
+        };
+
+        let size = metadata.len();
+        let modified = metadata.modified().unwrap_or(std::time::UNIX_EPOCH);
+        let last_modified = http_date(modified);
+        let etag = weak_etag(size, modified);
+
+        if is_fresh(headers, &etag, &last_modified) {
+            return Ok(Response::new(StatusCode::NOT_MODIFIED.into())
+                .set_header(header::ETAG, etag)
+                .set_header(header::LAST_MODIFIED, last_modified)
+                .set_header(header::ACCEPT_RANGES, "bytes"));
+        }
 
-        let resp = Response::new(StatusCode::OK.into())
-            .body(file)
-            .set_mime(mime);
+        let range = headers
+            .get(header::RANGE)
+            .and_then(|value| value.to_str().ok())
+            .map(|value| parse_range(value, size))
+            .unwrap_or(RangeRequest::None);
+
+        match range {
+            RangeRequest::Unsatisfiable => Ok(Response::new(StatusCode::RANGE_NOT_SATISFIABLE.into())
+                .set_header(header::CONTENT_RANGE, format!("bytes */{}", size))
+                .set_header(header::ACCEPT_RANGES, "bytes")),
+            RangeRequest::Satisfiable(range) => {
+                let content_range = format!("bytes {}-{}/{}", range.start, range.end - 1, size);
+                let content_length = range.end - range.start;
+                let body = if *method == Method::HEAD {
+                    Body::empty()
+                } else {
+                    Body::from_stream(new_file_stream(into_std_file(async_file), range))
+                };
+
+                let mut response = Response::new(StatusCode::PARTIAL_CONTENT.into())
+                    .body(body)
+                    .set_mime(mime)
+                    .set_header(header::ACCEPT_RANGES, "bytes")
+                    .set_header(header::CONTENT_RANGE, content_range)
+                    .set_header(header::CONTENT_LENGTH, content_length.to_string())
+                    .set_header(header::ETAG, etag)
+                    .set_header(header::LAST_MODIFIED, last_modified)
+                    .set_header(header::VARY, "Accept-Encoding");
+
+                if let Some(content_encoding) = content_encoding {
+                    response = response.set_header(header::CONTENT_ENCODING, content_encoding);
+                }
+
+                Ok(response)
+            }
+            RangeRequest::None => {
+                let body = if *method == Method::HEAD {
+                    Body::empty()
+                } else {
+                    Body::from_stream(new_file_stream(into_std_file(async_file), 0..size))
+                };
+
+                let mut response = Response::new(StatusCode::OK.into())
+                    .body(body)
+                    .set_mime(mime)
+                    .set_header(header::ACCEPT_RANGES, "bytes")
+                    .set_header(header::CONTENT_LENGTH, size.to_string())
+                    .set_header(header::ETAG, etag)
+                    .set_header(header::LAST_MODIFIED, last_modified)
+                    .set_header(header::VARY, "Accept-Encoding");
+
+                if let Some(content_encoding) = content_encoding {
+                    response = response.set_header(header::CONTENT_ENCODING, content_encoding);
+                }
+
+                Ok(response)
+            }
+        }
+    }
+
+    /// Render an HTML directory listing for `dir`.
+    async fn render_listing(&self, dir: &Path) -> Result<Response, Response> {
+        let mut entries = match async_std::fs::read_dir(dir).await {
+            Ok(entries) => entries,
+            Err(err) => {
+                log::warn!("Error reading directory: {:?}", err);
+                return Ok(self.server_error_response());
+            }
+        };
+
+        let mut items = String::new();
+
+        while let Some(entry) = entries.next().await {
+            let entry = match entry {
+                Ok(entry) => entry,
+                Err(err) => {
+                    log::warn!("Error reading directory entry: {:?}", err);
+                    return Ok(self.server_error_response());
+                }
+            };
+
+            let is_dir = entry
+                .file_type()
+                .await
+                .map(|file_type| file_type.is_dir())
+                .unwrap_or(false);
+
+            let name = entry.file_name().to_string_lossy().into_owned();
+            let display_name = if is_dir { format!("{}/", name) } else { name.clone() };
+            let href = if is_dir { format!("{}/", name) } else { name };
+
+            items.push_str(&format!(
+                "<li><a href=\"{}\">{}</a></li>",
+                utf8_percent_encode(&href, HREF_ENCODE_SET),
+                escape_html(&display_name),
+            ));
+        }
+
+        let body = format!(
+            "<!DOCTYPE html><html><body><ul><li><a href=\"../\">../</a></li>{}</ul></body></html>",
+            items
+        );
 
-        Ok(resp)
+        Ok(Response::new(StatusCode::OK.into())
+            .body(Body::from(body.into_bytes()))
+            .set_mime(mime::TEXT_HTML))
     }
 
     /// https://github.com/SergioBenitez/Rocket/blob/f857f81d9c156cbb6f8b24be173dbda0cb0504a0/core/http/src/uri/segments.rs#L65
@@ -100,11 +434,6 @@ impl StaticFiles {
     }
 }
 
-fn not_found_response() -> Response {
-    let response = Response::new(StatusCode::NOT_FOUND.into());
-    response
-}
-
 impl<S: 'static> tide::Endpoint<S> for StaticFiles {
 
     type Fut = futures::future::FutureObj<'static, Response>;
@@ -113,24 +442,22 @@ impl<S: 'static> tide::Endpoint<S> for StaticFiles {
         &self,
         req: Request<S>,
     ) -> Self::Fut {
-        if let Ok(path) = req.param::<String>("path") {
-            let path = path.to_owned();
-
-            // Necessary until async await in traits is available.
-            let cloned = self.clone();
-
-            futures::future::FutureObj::new(Box::new(
-                async move {
-                    let res = cloned.serve(&path).await;
-                    match res {
-                        Ok(response) => response,
-                        Err(response) => response,
-                    }
-                },
-            ))
-        } else {
-            unimplemented!("static file index")
-        }
+        // Requests that hit the mount point itself (e.g. `GET /assets/`) carry no `path` param;
+        // treat that as a request for the root of the served directory.
+        let path = req.param::<String>("path").unwrap_or_default();
+        let method = req.method().clone();
+        let headers = req.headers().clone();
+
+        // Necessary until async await in traits is available.
+        let cloned = self.clone();
+
+        futures::future::FutureObj::new(Box::new(async move {
+            let res = cloned.serve(&path, &method, &headers).await;
+            match res {
+                Ok(response) => response,
+                Err(response) => response,
+            }
+        }))
     }
 }
 
@@ -310,4 +637,219 @@ mod tests {
         assert_eq!(head.headers[CONTENT_TYPE], "text/html");
 
     }
+
+    #[test]
+    fn range_request_returns_206_partial_content() {
+        let (mut server, dir) = test_app("/static/*path");
+
+        let file_path = dir.path().join("meow.pdf");
+        let mut file = File::create(file_path).unwrap();
+        write!(file, "{}", "says the cat").unwrap();
+
+        let req = http::Request::builder()
+            .uri("/static/meow.pdf")
+            .header(http::header::RANGE, "bytes=0-3")
+            .body(http_service::Body::empty())
+            .unwrap();
+
+        let (head, body) = server.simulate(req).unwrap();
+
+        assert_eq!(head.status, 206);
+        assert_eq!(head.headers[http::header::CONTENT_RANGE], "bytes 0-3/12");
+        assert_eq!(head.headers[http::header::CONTENT_LENGTH], "4");
+        assert_eq!(String::from_utf8(body).unwrap(), "says");
+    }
+
+    #[test]
+    fn unsatisfiable_range_returns_416() {
+        let (mut server, dir) = test_app("/static/*path");
+
+        let file_path = dir.path().join("meow.pdf");
+        let mut file = File::create(file_path).unwrap();
+        write!(file, "{}", "says the cat").unwrap();
+
+        let req = http::Request::builder()
+            .uri("/static/meow.pdf")
+            .header(http::header::RANGE, "bytes=9000-")
+            .body(http_service::Body::empty())
+            .unwrap();
+
+        let (head, body) = server.simulate(req).unwrap();
+
+        assert_eq!(head.status, 416);
+        assert_eq!(head.headers[http::header::CONTENT_RANGE], "bytes */12");
+        assert_eq!(body, Vec::<u8>::new());
+    }
+
+    #[test]
+    fn conditional_get_with_matching_etag_returns_304() {
+        let (mut server, dir) = test_app("/static/*path");
+
+        let file_path = dir.path().join("meow.pdf");
+        let mut file = File::create(file_path).unwrap();
+        write!(file, "{}", "says the cat").unwrap();
+
+        let req = http::Request::builder()
+            .uri("/static/meow.pdf")
+            .body(http_service::Body::empty())
+            .unwrap();
+        let (head, _) = server.simulate(req).unwrap();
+        let etag = head.headers[http::header::ETAG].clone();
+
+        let req = http::Request::builder()
+            .uri("/static/meow.pdf")
+            .header(http::header::IF_NONE_MATCH, etag)
+            .body(http_service::Body::empty())
+            .unwrap();
+        let (head, body) = server.simulate(req).unwrap();
+
+        assert_eq!(head.status, 304);
+        assert_eq!(body, Vec::<u8>::new());
+    }
+
+    #[test]
+    fn conditional_get_with_stale_etag_returns_200() {
+        let (mut server, dir) = test_app("/static/*path");
+
+        let file_path = dir.path().join("meow.pdf");
+        let mut file = File::create(file_path).unwrap();
+        write!(file, "{}", "says the cat").unwrap();
+
+        let req = http::Request::builder()
+            .uri("/static/meow.pdf")
+            .header(http::header::IF_NONE_MATCH, "W/\"not-the-right-one\"")
+            .body(http_service::Body::empty())
+            .unwrap();
+        let (head, body) = server.simulate(req).unwrap();
+
+        assert_eq!(head.status, 200);
+        assert_eq!(String::from_utf8(body).unwrap(), "says the cat");
+    }
+
+    #[test]
+    fn directory_listing_shows_entries_and_escapes_hrefs() {
+        let mut app = tide::new();
+        let temp_dir = TempDir::new().unwrap();
+        let endpoint =
+            StaticFiles::new(&format!("{}", temp_dir.path().to_string_lossy())).show_listing(true);
+        app.at("/static/*path").get(endpoint);
+        let mut server = MockServer {
+            backend: app.into_http_service(),
+        };
+
+        let file_path = temp_dir.path().join("a&b.txt");
+        File::create(file_path).unwrap();
+
+        let req = http::Request::builder()
+            .uri("/static/")
+            .body(http_service::Body::empty())
+            .unwrap();
+
+        let (head, body) = server.simulate(req).unwrap();
+        let body = String::from_utf8(body).unwrap();
+
+        assert_eq!(head.status, 200);
+        assert!(body.contains("href=\"a%26b.txt\""));
+        assert!(body.contains(">a&amp;b.txt<"));
+    }
+
+    #[test]
+    fn precompressed_gzip_sibling_is_served_when_accepted() {
+        let (mut server, dir) = test_app("/static/*path");
+
+        let mut file = File::create(dir.path().join("app.js")).unwrap();
+        write!(file, "{}", "plain").unwrap();
+        let mut gz_file = File::create(dir.path().join("app.js.gz")).unwrap();
+        write!(gz_file, "{}", "gzipped").unwrap();
+
+        let req = http::Request::builder()
+            .uri("/static/app.js")
+            .header(http::header::ACCEPT_ENCODING, "gzip")
+            .body(http_service::Body::empty())
+            .unwrap();
+
+        let (head, body) = server.simulate(req).unwrap();
+
+        assert_eq!(head.status, 200);
+        assert_eq!(head.headers[http::header::CONTENT_ENCODING], "gzip");
+        assert_eq!(String::from_utf8(body).unwrap(), "gzipped");
+    }
+
+    #[test]
+    fn precompressed_sibling_is_ignored_when_not_accepted() {
+        let (mut server, dir) = test_app("/static/*path");
+
+        let mut file = File::create(dir.path().join("app.js")).unwrap();
+        write!(file, "{}", "plain").unwrap();
+        let mut gz_file = File::create(dir.path().join("app.js.gz")).unwrap();
+        write!(gz_file, "{}", "gzipped").unwrap();
+
+        let req = http::Request::builder()
+            .uri("/static/app.js")
+            .body(http_service::Body::empty())
+            .unwrap();
+
+        let (head, body) = server.simulate(req).unwrap();
+
+        assert_eq!(head.status, 200);
+        assert!(!head.headers.contains_key(http::header::CONTENT_ENCODING));
+        assert_eq!(String::from_utf8(body).unwrap(), "plain");
+    }
+
+    #[test]
+    fn head_request_sets_content_length_with_empty_body() {
+        let mut app = tide::new();
+        let temp_dir = TempDir::new().unwrap();
+        let endpoint = StaticFiles::new(&format!("{}", temp_dir.path().to_string_lossy()));
+        app.at("/static/*path").get(endpoint.clone()).head(endpoint);
+        let mut server = MockServer {
+            backend: app.into_http_service(),
+        };
+
+        let mut file = File::create(temp_dir.path().join("meow.pdf")).unwrap();
+        write!(file, "{}", "says the cat").unwrap();
+
+        let req = http::Request::builder()
+            .method(http::Method::HEAD)
+            .uri("/static/meow.pdf")
+            .body(http_service::Body::empty())
+            .unwrap();
+
+        let (head, body) = server.simulate(req).unwrap();
+
+        assert_eq!(head.status, 200);
+        assert_eq!(head.headers[http::header::CONTENT_LENGTH], "12");
+        assert_eq!(body, Vec::<u8>::new());
+    }
+
+    #[test]
+    fn not_found_fallback_serves_spa_entry_point_with_200() {
+        let mut app = tide::new();
+        let temp_dir = TempDir::new().unwrap();
+
+        let index_path = temp_dir.path().join("index.html");
+        let mut index_file = File::create(&index_path).unwrap();
+        write!(index_file, "{}", "<html>the app shell</html>").unwrap();
+
+        let endpoint = StaticFiles::new(&format!("{}", temp_dir.path().to_string_lossy()))
+            .not_found_fallback(index_path)
+            .not_found_status(StatusCode::OK);
+        app.at("/static/*path").get(endpoint);
+        let mut server = MockServer {
+            backend: app.into_http_service(),
+        };
+
+        let req = http::Request::builder()
+            .uri("/static/some/deep/link")
+            .body(http_service::Body::empty())
+            .unwrap();
+
+        let (head, body) = server.simulate(req).unwrap();
+
+        assert_eq!(head.status, 200);
+        assert_eq!(
+            String::from_utf8(body).unwrap(),
+            "<html>the app shell</html>"
+        );
+    }
 }
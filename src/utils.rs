@@ -1,49 +1,393 @@
-use http::{header, StatusCode};
-use http_service::Body;
+use http::{header, HeaderMap};
 use std::{
     cmp::min,
+    ops::Range,
     path::{Path, PathBuf},
+    time::SystemTime,
 };
 
 const MAX_BUFFER_SIZE: usize = 1024 * 1024 * 10;
 
-/// Given request url path and base directory
+pub fn buffer_size(remain: u64) -> usize {
+    min(remain as usize, MAX_BUFFER_SIZE)
+}
+
+/// The outcome of parsing a request's `Range` header against the size of the file it applies to.
+pub enum RangeRequest {
+    /// No `Range` header was present, or it could not be parsed (in which case we fall back to a
+    /// normal, full-body response rather than erroring).
+    None,
+    /// A single, satisfiable byte range.
+    Satisfiable(Range<u64>),
+    /// The requested range starts at or past the end of the file.
+    Unsatisfiable,
+}
+
+/// Parse a `Range: bytes=...` header value against the size of the file it applies to.
 ///
-/// Return `None` if the request might be a path traversal attack.
-pub fn resolve_path(base: &Path, url_path: &str) -> Option<PathBuf> {
-    let mut addition = PathBuf::new();
-    // TODO work with urlencode
-    // TODO With urlencode, component might contain '\', which could be different on Linux and Windows
-    for component in url_path.split('/') {
-        match component {
-            "." => continue,
-            ".." => {
-                if !addition.pop() {
-                    return None;
-                }
+/// Only a single range is supported; anything containing a comma (multiple ranges) is treated as
+/// absent, same as a header we don't recognize at all.
+pub fn parse_range(header_value: &str, file_size: u64) -> RangeRequest {
+    let spec = match header_value.strip_prefix("bytes=") {
+        Some(spec) if !spec.contains(',') => spec,
+        _ => return RangeRequest::None,
+    };
+
+    let (start, end) = match spec.find('-') {
+        Some(index) => (&spec[..index], &spec[index + 1..]),
+        None => return RangeRequest::None,
+    };
+
+    let range = if start.is_empty() {
+        // `bytes=-500`: the last 500 bytes of the file. A suffix range over an empty file has
+        // nothing to satisfy.
+        match end.parse::<u64>() {
+            Ok(suffix_length) if suffix_length > 0 && file_size > 0 => {
+                let suffix_length = min(suffix_length, file_size);
+                file_size - suffix_length..file_size
+            }
+            Ok(_) => return RangeRequest::Unsatisfiable,
+            Err(_) => return RangeRequest::None,
+        }
+    } else {
+        let start: u64 = match start.parse() {
+            Ok(start) => start,
+            Err(_) => return RangeRequest::None,
+        };
+
+        if start >= file_size {
+            return RangeRequest::Unsatisfiable;
+        }
+
+        let end = if end.is_empty() {
+            // `bytes=500-`: from 500 to the end of the file.
+            file_size
+        } else {
+            match end.parse::<u64>() {
+                // The end bound is inclusive, so the exclusive range end is `end + 1`. Saturate
+                // rather than overflow on a crafted `end` of `u64::MAX`.
+                Ok(end) => min(end.saturating_add(1), file_size),
+                Err(_) => return RangeRequest::None,
             }
-            _ => addition.push(component),
+        };
+
+        // A reversed range (last-byte-pos < first-byte-pos) is invalid per RFC 7233; ignore it
+        // rather than let callers underflow on `range.end - range.start`.
+        if start >= end {
+            return RangeRequest::None;
+        }
+
+        start..end
+    };
+
+    RangeRequest::Satisfiable(range)
+}
+
+/// Format a modification time as an RFC 7231 HTTP-date, for use in `Last-Modified`.
+pub fn http_date(time: SystemTime) -> String {
+    httpdate::fmt_http_date(time)
+}
+
+/// A weak validator derived from the file's size and modification time, cheap to compute without
+/// reading the file's contents.
+pub fn weak_etag(len: u64, modified: SystemTime) -> String {
+    let mtime_secs = modified
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0);
+
+    format!("W/\"{}-{}\"", len, mtime_secs)
+}
+
+/// Whether the client's cached copy, described by `If-None-Match` / `If-Modified-Since`, is still
+/// fresh and the request can be answered with `304 Not Modified`.
+pub fn is_fresh(headers: &HeaderMap, etag: &str, last_modified: &str) -> bool {
+    if let Some(if_none_match) = headers
+        .get(header::IF_NONE_MATCH)
+        .and_then(|value| value.to_str().ok())
+    {
+        return if_none_match
+            .split(',')
+            .any(|candidate| candidate.trim() == etag || candidate.trim() == "*");
+    }
+
+    if let Some(if_modified_since) = headers
+        .get(header::IF_MODIFIED_SINCE)
+        .and_then(|value| value.to_str().ok())
+    {
+        if let (Ok(since), Ok(modified)) = (
+            httpdate::parse_http_date(if_modified_since),
+            httpdate::parse_http_date(last_modified),
+        ) {
+            return modified <= since;
         }
     }
-    Some(base.join(addition))
+
+    false
 }
 
-pub fn buffer_size(remain: u64) -> usize {
-    min(remain as usize, MAX_BUFFER_SIZE)
+/// Which precompressed content-codings, if any, a client accepts according to its
+/// `Accept-Encoding` header.
+///
+/// This only records acceptability, not relative preference: a non-zero `q` of any weight counts
+/// as accepted, so e.g. `br;q=0.1, gzip;q=0.9` still reports both as accepted even though the
+/// client weights gzip far higher. Callers that pick between `br` and `gzip` use a fixed
+/// preference order (brotli over gzip), not the `q` weights.
+#[derive(Default)]
+pub struct AcceptedEncodings {
+    pub br: bool,
+    pub gzip: bool,
 }
 
-pub fn not_found_response() -> http::Response<http_service::Body> {
-    http::response::Builder::new()
-        .status(StatusCode::NOT_FOUND)
-        .header(header::CONTENT_TYPE, mime::TEXT_PLAIN_UTF_8.to_string())
-        .body(Body::from("not found"))
-        .unwrap()
+/// Parse an `Accept-Encoding` header value, honoring `q=0` as "not acceptable". Non-zero `q`
+/// values are not otherwise ranked — see [`AcceptedEncodings`].
+pub fn parse_accept_encoding(header_value: &str) -> AcceptedEncodings {
+    let mut accepted = AcceptedEncodings::default();
+
+    for part in header_value.split(',') {
+        let mut segments = part.split(';');
+        let coding = segments.next().unwrap_or("").trim();
+
+        let q: f32 = segments
+            .find_map(|param| param.trim().strip_prefix("q="))
+            .and_then(|q| q.parse().ok())
+            .unwrap_or(1.0);
+
+        if q <= 0.0 {
+            continue;
+        }
+
+        match coding {
+            "br" => accepted.br = true,
+            "gzip" => accepted.gzip = true,
+            _ => {}
+        }
+    }
+
+    accepted
 }
 
-pub fn server_error_response() -> http::Response<http_service::Body> {
-    http::response::Builder::new()
-        .status(StatusCode::INTERNAL_SERVER_ERROR)
-        .header(header::CONTENT_TYPE, mime::TEXT_PLAIN_UTF_8.to_string())
-        .body(Body::from("not found"))
-        .unwrap()
+/// The path of the precompressed sibling of `path` for a given content-coding, e.g.
+/// `app.js` -> `app.js.br`.
+pub fn precompressed_sibling(path: &Path, extension: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".");
+    name.push(extension);
+    PathBuf::from(name)
+}
+
+/// Escape the characters that are significant in HTML text content, for use in the directory
+/// listing.
+pub fn escape_html(input: &str) -> String {
+    let mut escaped = String::with_capacity(input.len());
+
+    for c in input.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn satisfiable(range: RangeRequest) -> Range<u64> {
+        match range {
+            RangeRequest::Satisfiable(range) => range,
+            RangeRequest::None => panic!("expected a satisfiable range, got None"),
+            RangeRequest::Unsatisfiable => panic!("expected a satisfiable range, got Unsatisfiable"),
+        }
+    }
+
+    #[test]
+    fn parse_range_explicit_bounds() {
+        assert_eq!(satisfiable(parse_range("bytes=0-1023", 2048)), 0..1024);
+    }
+
+    #[test]
+    fn parse_range_open_ended_start() {
+        assert_eq!(satisfiable(parse_range("bytes=500-", 1000)), 500..1000);
+    }
+
+    #[test]
+    fn parse_range_suffix() {
+        assert_eq!(satisfiable(parse_range("bytes=-500", 1000)), 500..1000);
+    }
+
+    #[test]
+    fn parse_range_suffix_longer_than_file_is_clamped() {
+        assert_eq!(satisfiable(parse_range("bytes=-5000", 1000)), 0..1000);
+    }
+
+    #[test]
+    fn parse_range_suffix_on_empty_file_is_unsatisfiable() {
+        assert!(matches!(
+            parse_range("bytes=-500", 0),
+            RangeRequest::Unsatisfiable
+        ));
+    }
+
+    #[test]
+    fn parse_range_start_past_end_is_unsatisfiable() {
+        assert!(matches!(
+            parse_range("bytes=2000-", 1000),
+            RangeRequest::Unsatisfiable
+        ));
+    }
+
+    #[test]
+    fn parse_range_without_bytes_prefix_is_ignored() {
+        assert!(matches!(parse_range("items=0-5", 1000), RangeRequest::None));
+    }
+
+    #[test]
+    fn parse_range_multiple_ranges_are_ignored() {
+        assert!(matches!(
+            parse_range("bytes=0-10,20-30", 1000),
+            RangeRequest::None
+        ));
+    }
+
+    #[test]
+    fn parse_range_reversed_bounds_are_ignored() {
+        assert!(matches!(
+            parse_range("bytes=500-100", 1000),
+            RangeRequest::None
+        ));
+    }
+
+    #[test]
+    fn parse_range_end_at_u64_max_does_not_overflow() {
+        assert_eq!(
+            satisfiable(parse_range("bytes=0-18446744073709551615", 1000)),
+            0..1000
+        );
+    }
+
+    #[test]
+    fn weak_etag_is_derived_from_size_and_mtime() {
+        let modified = SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(42);
+        assert_eq!(weak_etag(12, modified), "W/\"12-42\"");
+    }
+
+    #[test]
+    fn weak_etag_falls_back_to_zero_for_a_time_before_the_epoch() {
+        let modified = SystemTime::UNIX_EPOCH - std::time::Duration::from_secs(1);
+        assert_eq!(weak_etag(12, modified), "W/\"12-0\"");
+    }
+
+    fn headers(pairs: &[(header::HeaderName, &str)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (name, value) in pairs {
+            headers.insert(name.clone(), value.parse().unwrap());
+        }
+        headers
+    }
+
+    #[test]
+    fn is_fresh_matches_on_if_none_match() {
+        let headers = headers(&[(header::IF_NONE_MATCH, "W/\"12-42\"")]);
+        assert!(is_fresh(&headers, "W/\"12-42\"", "irrelevant"));
+    }
+
+    #[test]
+    fn is_fresh_matches_wildcard_if_none_match() {
+        let headers = headers(&[(header::IF_NONE_MATCH, "*")]);
+        assert!(is_fresh(&headers, "W/\"12-42\"", "irrelevant"));
+    }
+
+    #[test]
+    fn is_fresh_if_none_match_mismatch_is_not_fresh() {
+        let headers = headers(&[(header::IF_NONE_MATCH, "W/\"99-1\"")]);
+        assert!(!is_fresh(&headers, "W/\"12-42\"", "irrelevant"));
+    }
+
+    #[test]
+    fn is_fresh_falls_back_to_if_modified_since() {
+        let last_modified = "Sun, 06 Nov 1994 08:49:37 GMT";
+        let headers = headers(&[(header::IF_MODIFIED_SINCE, last_modified)]);
+        assert!(is_fresh(&headers, "W/\"12-42\"", last_modified));
+    }
+
+    #[test]
+    fn is_fresh_if_modified_since_in_the_future_is_fresh() {
+        let headers = headers(&[(
+            header::IF_MODIFIED_SINCE,
+            "Sun, 06 Nov 2094 08:49:37 GMT",
+        )]);
+        assert!(is_fresh(
+            &headers,
+            "W/\"12-42\"",
+            "Sun, 06 Nov 1994 08:49:37 GMT"
+        ));
+    }
+
+    #[test]
+    fn is_fresh_if_modified_since_older_than_last_modified_is_not_fresh() {
+        let headers = headers(&[(
+            header::IF_MODIFIED_SINCE,
+            "Sun, 06 Nov 1994 08:49:37 GMT",
+        )]);
+        assert!(!is_fresh(
+            &headers,
+            "W/\"12-42\"",
+            "Sun, 06 Nov 2094 08:49:37 GMT"
+        ));
+    }
+
+    #[test]
+    fn is_fresh_without_conditional_headers_is_not_fresh() {
+        assert!(!is_fresh(&HeaderMap::new(), "W/\"12-42\"", "irrelevant"));
+    }
+
+    #[test]
+    fn escape_html_escapes_significant_characters() {
+        assert_eq!(
+            escape_html("a & b <c> \"d\" 'e'"),
+            "a &amp; b &lt;c&gt; &quot;d&quot; &#39;e&#39;"
+        );
+    }
+
+    #[test]
+    fn escape_html_leaves_plain_text_untouched() {
+        assert_eq!(escape_html("meow.pdf"), "meow.pdf");
+    }
+
+    #[test]
+    fn parse_accept_encoding_accepts_br_and_gzip() {
+        let accepted = parse_accept_encoding("br, gzip");
+        assert!(accepted.br);
+        assert!(accepted.gzip);
+    }
+
+    #[test]
+    fn parse_accept_encoding_ignores_unknown_codings() {
+        let accepted = parse_accept_encoding("deflate, identity");
+        assert!(!accepted.br);
+        assert!(!accepted.gzip);
+    }
+
+    #[test]
+    fn parse_accept_encoding_honors_q_zero() {
+        let accepted = parse_accept_encoding("br;q=0, gzip;q=0.5");
+        assert!(!accepted.br);
+        assert!(accepted.gzip);
+    }
+
+    #[test]
+    fn parse_accept_encoding_any_nonzero_q_counts_as_accepted() {
+        // A low `q` is still acceptance, not a preference signal: both are reported as accepted
+        // even though the client weights gzip far higher than br.
+        let accepted = parse_accept_encoding("br;q=0.1, gzip;q=0.9");
+        assert!(accepted.br);
+        assert!(accepted.gzip);
+    }
 }